@@ -4,6 +4,7 @@ use std::{path::PathBuf, time::Duration};
 
 use anyhow::Context;
 use clap::{CommandFactory, Parser};
+use serde::Serialize;
 
 #[derive(clap::Parser)]
 #[clap(name = "json-patch")]
@@ -28,46 +29,417 @@ enum Command {
 struct DiffCommand {
     from: PathBuf,
     to: PathBuf,
+    /// Produce an RFC 7386 JSON Merge Patch instead of an RFC 6902 operation array.
+    #[arg(long)]
+    merge: bool,
+    /// Override the output indentation: a number of spaces, or "tab".
+    ///
+    /// Defaults to the indentation inferred from the `from` document.
+    #[arg(long)]
+    indent: Option<String>,
+    /// Print the SHA-256 digest of the `from` document to stderr, for pairing
+    /// with `apply --expect-sha256`.
+    #[arg(long)]
+    print_sha256: bool,
+}
+
+/// Infer the indentation unit of a pretty-printed JSON document from its raw bytes.
+///
+/// Detects a leading tab, otherwise counts the leading spaces on the first
+/// indented line, falling back to two spaces when nothing is indented.
+fn infer_indent(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    for line in text.lines() {
+        if line.starts_with('\t') {
+            return "\t".to_string();
+        }
+        let spaces = line.chars().take_while(|c| *c == ' ').count();
+        if spaces > 0 {
+            return " ".repeat(spaces);
+        }
+    }
+    "  ".to_string()
+}
+
+/// Resolve the indentation string from an optional `--indent` override (a count
+/// of spaces or the literal `tab`), otherwise inferring it from `raw`.
+fn resolve_indent(indent: Option<&str>, raw: &[u8]) -> anyhow::Result<String> {
+    match indent {
+        Some(value) if value.eq_ignore_ascii_case("tab") => Ok("\t".to_string()),
+        Some(value) => {
+            let n: usize = value
+                .parse()
+                .context("Error parsing --indent: expected a number or \"tab\"")?;
+            Ok(" ".repeat(n))
+        }
+        None => Ok(infer_indent(raw)),
+    }
+}
+
+/// Serialize `value` as pretty JSON with the given indentation string, restoring
+/// a trailing newline when the source document had one.
+fn serialize_pretty(
+    value: &serde_json::Value,
+    indent: &str,
+    trailing_newline: bool,
+) -> anyhow::Result<String> {
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    value
+        .serialize(&mut serializer)
+        .context("Error serializing output")?;
+    let mut output = String::from_utf8(buf).context("Error serializing output as utf-8")?;
+    if trailing_newline {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Serialize `value` canonically — compact, with object keys sorted — for hashing.
+fn canonical_bytes(value: &serde_json::Value) -> Vec<u8> {
+    fn canonical(value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut sorted = serde_json::Map::new();
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                for key in keys {
+                    sorted.insert(key.clone(), canonical(&map[key]));
+                }
+                serde_json::Value::Object(sorted)
+            }
+            serde_json::Value::Array(arr) => {
+                serde_json::Value::Array(arr.iter().map(canonical).collect())
+            }
+            other => other.clone(),
+        }
+    }
+    serde_json::to_vec(&canonical(value)).expect("canonical serialization cannot fail")
+}
+
+/// Compute the SHA-256 digest of a value's canonical serialization, as lowercase hex.
+fn sha256_hex(value: &serde_json::Value) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(canonical_bytes(value));
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Persisted defaults loaded from a `json-patch.toml` file. Every key is
+/// optional; a missing key leaves the corresponding built-in default in place.
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    editor: Option<String>,
+    pager: Option<String>,
+    indent: Option<String>,
+    watch_interval_ms: Option<u64>,
+}
+
+/// Load configuration from an explicit `--config` path, or the platform config
+/// directory's `json-patch.toml` when no override is given.
+///
+/// An explicit path that cannot be read is an error; a missing default file is not.
+fn load_config(path: Option<&std::path::Path>) -> anyhow::Result<Config> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match dirs::config_dir().map(|dir| dir.join("json-patch.toml")) {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Config::default()),
+        },
+    };
+    let contents = std::fs::read_to_string(&path).context("Error reading config file")?;
+    toml::from_str(&contents).context("Error parsing config file as toml")
+}
+
+/// Compute an RFC 7386 JSON Merge Patch describing the change from `from` to `to`.
+///
+/// When both sides are objects only the added/changed keys are emitted, with a
+/// `null` for keys that were removed; otherwise the whole `to` value is used.
+fn merge_diff(from: &serde_json::Value, to: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            let mut patch = serde_json::Map::new();
+            for (key, to_value) in to {
+                match from.get(key) {
+                    Some(from_value) if from_value == to_value => {}
+                    Some(from_value) => {
+                        patch.insert(key.clone(), merge_diff(from_value, to_value));
+                    }
+                    None => {
+                        patch.insert(key.clone(), to_value.clone());
+                    }
+                }
+            }
+            for key in from.keys() {
+                if !to.contains_key(key) {
+                    patch.insert(key.clone(), Value::Null);
+                }
+            }
+            Value::Object(patch)
+        }
+        _ => to.clone(),
+    }
 }
 
 fn diff_impl(command: DiffCommand) -> anyhow::Result<String> {
+    let from_raw = std::fs::read(command.from).context("Error reading from file")?;
     let from: serde_json::Value =
-        serde_json::from_slice(&std::fs::read(command.from).context("Error reading from file")?)
-            .context("Error parsing from file as json")?;
+        serde_json::from_slice(&from_raw).context("Error parsing from file as json")?;
     let to: serde_json::Value =
         serde_json::from_slice(&std::fs::read(command.to).context("Error reading to file")?)
             .context("Error parsing to file as json")?;
-    let patch = json_patch::diff(&from, &to);
-    serde_json::to_string_pretty(&patch).context("Error serializing patch")
+    if command.print_sha256 {
+        eprintln!("{}", sha256_hex(&from));
+    }
+    let indent = resolve_indent(command.indent.as_deref(), &from_raw)?;
+    let trailing_newline = from_raw.ends_with(b"\n");
+    if command.merge {
+        let patch = merge_diff(&from, &to);
+        serialize_pretty(&patch, &indent, trailing_newline)
+    } else {
+        let patch = serde_json::to_value(json_patch::diff(&from, &to))
+            .context("Error serializing patch")?;
+        serialize_pretty(&patch, &indent, trailing_newline)
+    }
 }
 
 fn diff(command: DiffCommand) -> anyhow::Result<()> {
     let patch_string = diff_impl(command)?;
-    println!("{patch_string}");
+    // `patch_string` already carries the source's trailing newline (if any);
+    // use `print!` so stdout matches the patch file that `edit` writes exactly.
+    print!("{patch_string}");
     Ok(())
 }
 
 #[derive(clap::Args)]
 struct ApplyCommand {
-    input: PathBuf,
+    /// Input JSON documents to patch. Accepts multiple paths or a glob pattern.
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
     #[arg(short, long)]
     patch: PathBuf,
+    /// Treat the patch file as an RFC 7386 JSON Merge Patch instead of an RFC 6902 operation array.
+    #[arg(long)]
+    merge: bool,
+    /// Override the output indentation: a number of spaces, or "tab".
+    ///
+    /// Defaults to the indentation inferred from the input document.
+    #[arg(long)]
+    indent: Option<String>,
+    /// Write the patched result back to each input file instead of printing to stdout.
+    #[arg(short, long)]
+    in_place: bool,
+    /// Render a colorized inline diff of each change without modifying anything.
+    #[arg(long)]
+    dry_run: bool,
+    /// Cherry-pick which RFC 6902 operations to apply using `fzf`.
+    #[arg(long)]
+    interactive: bool,
+    /// Refuse to patch unless the input document's SHA-256 digest matches this
+    /// hex value, guarding against applying a patch to a drifted document.
+    #[arg(long)]
+    expect_sha256: Option<String>,
+}
+
+/// Render a single RFC 6902 operation as a human-readable line for selection.
+fn describe_operation(op: &json_patch::PatchOperation) -> String {
+    use json_patch::PatchOperation::*;
+    fn summarize(value: &serde_json::Value) -> String {
+        let s = value.to_string();
+        if s.chars().count() > 60 {
+            format!("{}…", s.chars().take(60).collect::<String>())
+        } else {
+            s
+        }
+    }
+    match op {
+        Add(o) => format!("add     {} = {}", o.path, summarize(&o.value)),
+        Remove(o) => format!("remove  {}", o.path),
+        Replace(o) => format!("replace {} = {}", o.path, summarize(&o.value)),
+        Move(o) => format!("move    {} -> {}", o.from, o.path),
+        Copy(o) => format!("copy    {} -> {}", o.from, o.path),
+        Test(o) => format!("test    {} = {}", o.path, summarize(&o.value)),
+    }
+}
+
+/// Present each operation of `patch` to `fzf --multi` and return a reduced
+/// `Patch` containing only the operations the user selected.
+fn select_operations(patch: &json_patch::Patch) -> anyhow::Result<json_patch::Patch> {
+    use std::io::Write;
+    let menu: String = patch
+        .0
+        .iter()
+        .enumerate()
+        .map(|(idx, op)| format!("{idx}\t{}", describe_operation(op)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut child = std::process::Command::new("fzf")
+        .arg("--multi")
+        .arg("--delimiter=\t")
+        .arg("--with-nth=2..")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Error spawning fzf (is it installed and on PATH?)")?;
+    child
+        .stdin
+        .take()
+        .context("Error opening fzf stdin")?
+        .write_all(menu.as_bytes())
+        .context("Error writing operations to fzf")?;
+    let output = child
+        .wait_with_output()
+        .context("Error waiting for fzf")?;
+
+    // A non-zero exit means the user cancelled (e.g. Esc → 130); bail rather
+    // than treating it as an empty selection and applying a no-op patch.
+    if !output.status.success() {
+        anyhow::bail!("Operation selection cancelled");
+    }
+
+    let selected: Vec<json_patch::PatchOperation> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split('\t').next()?.parse::<usize>().ok())
+        .filter_map(|idx| patch.0.get(idx).cloned())
+        .collect();
+    Ok(json_patch::Patch(selected))
 }
 
-fn apply_impl(command: ApplyCommand) -> anyhow::Result<String> {
+/// Expand input arguments, treating any entry containing glob metacharacters as
+/// a pattern and leaving the rest as literal paths.
+fn expand_inputs(inputs: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        let pattern = input.to_string_lossy();
+        if pattern.contains(['*', '?', '[']) {
+            let mut matched = 0;
+            for entry in glob::glob(&pattern).context("Error parsing glob pattern")? {
+                paths.push(entry.context("Error matching glob pattern")?);
+                matched += 1;
+            }
+            if matched == 0 {
+                anyhow::bail!("Glob pattern matched no files: {pattern}");
+            }
+        } else {
+            paths.push(input.clone());
+        }
+    }
+    Ok(paths)
+}
+
+/// Apply an RFC 7386 JSON Merge Patch to `target` in place.
+///
+/// A `null` value deletes the corresponding key; a non-object patch replaces
+/// the target outright; otherwise each key is merged recursively.
+fn merge_apply(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    use serde_json::Value;
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(serde_json::Map::new());
+            }
+            let map = target.as_object_mut().expect("target coerced to object above");
+            for (key, value) in patch {
+                if value.is_null() {
+                    map.remove(key);
+                } else {
+                    merge_apply(map.entry(key.clone()).or_insert(Value::Null), value);
+                }
+            }
+        }
+        _ => *target = patch.clone(),
+    }
+}
+
+fn apply_impl(
+    input: &std::path::Path,
+    patch: &std::path::Path,
+    merge: bool,
+    indent: Option<&str>,
+    expect_sha256: Option<&str>,
+) -> anyhow::Result<String> {
+    let input_raw = std::fs::read(input).context("Error reading from file")?;
     let mut document: serde_json::Value =
-        serde_json::from_slice(&std::fs::read(command.input).context("Error reading from file")?)
-            .context("Error parsing input file as json")?;
-    let patch: json_patch::Patch =
-        serde_json::from_slice(&std::fs::read(command.patch).context("Error reading patch file")?)
-            .context("Error parsing patch file as json")?;
-    json_patch::patch(&mut document, &patch).context("Error applying patch")?;
-    serde_json::to_string_pretty(&document).context("Error serializing output")
+        serde_json::from_slice(&input_raw).context("Error parsing input file as json")?;
+    if let Some(expected) = expect_sha256 {
+        let actual = sha256_hex(&document);
+        if !actual.eq_ignore_ascii_case(expected) {
+            anyhow::bail!(
+                "SHA-256 mismatch for {}: expected {expected}, found {actual}",
+                input.display()
+            );
+        }
+    }
+    if merge {
+        let patch: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(patch).context("Error reading patch file")?)
+                .context("Error parsing patch file as json")?;
+        merge_apply(&mut document, &patch);
+    } else {
+        let patch: json_patch::Patch =
+            serde_json::from_slice(&std::fs::read(patch).context("Error reading patch file")?)
+                .context("Error parsing patch file as json")?;
+        json_patch::patch(&mut document, &patch).context("Error applying patch")?;
+    }
+    let indent = resolve_indent(indent, &input_raw)?;
+    serialize_pretty(&document, &indent, input_raw.ends_with(b"\n"))
 }
 
 fn apply(command: ApplyCommand) -> anyhow::Result<()> {
-    let output_string = apply_impl(command)?;
-    println!("{output_string}");
+    let inputs = expand_inputs(&command.inputs)?;
+
+    // Optionally narrow the patch to a user-selected subset of operations. The
+    // reduced patch is written to a temporary file reused across all inputs.
+    let mut _selection = None;
+    let patch_path = if command.interactive {
+        if command.merge {
+            anyhow::bail!("--interactive is only supported for RFC 6902 patches");
+        }
+        let patch: json_patch::Patch = serde_json::from_slice(
+            &std::fs::read(&command.patch).context("Error reading patch file")?,
+        )
+        .context("Error parsing patch file as json")?;
+        let reduced = select_operations(&patch)?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("patch.json");
+        std::fs::write(
+            &path,
+            serde_json::to_string(&reduced).context("Error serializing patch")?,
+        )?;
+        _selection = Some(dir);
+        path
+    } else {
+        command.patch.clone()
+    };
+
+    for input in inputs {
+        let output = apply_impl(
+            &input,
+            &patch_path,
+            command.merge,
+            command.indent.as_deref(),
+            command.expect_sha256.as_deref(),
+        )?;
+        if command.dry_run {
+            let original = std::fs::read_to_string(&input).context("Error reading input")?;
+            println!("{}", style(input.display()).bold());
+            write_paged(&render_inline_diff(&original, &output), None)?;
+        } else if command.in_place {
+            std::fs::write(&input, &output).context("Error writing patched file")?;
+        } else {
+            // `output` already carries the source's trailing newline (if any);
+            // `print!` keeps stdout identical to the `--in-place` file contents.
+            print!("{output}");
+        }
+    }
     Ok(())
 }
 
@@ -82,8 +454,30 @@ struct EditCommand {
     /// If the patch file does not yet exist, this command will create a new one.
     #[arg(short, long)]
     patch: PathBuf,
-    #[arg(short, long, default_value = "vim")]
-    editor: String,
+    /// Text editor used to edit the patched document.
+    ///
+    /// Overrides the `editor` config key; defaults to `vim`.
+    #[arg(short, long)]
+    editor: Option<String>,
+    /// Treat the patch as an RFC 7386 JSON Merge Patch instead of an RFC 6902 operation array.
+    #[arg(long)]
+    merge: bool,
+    /// Format used to render the diff.
+    #[arg(long, value_enum, default_value_t = DiffFormat::Inline)]
+    format: DiffFormat,
+    /// Path to a `json-patch.toml` config file, overriding the platform default location.
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+/// How a diff is rendered for display.
+#[derive(clap::ValueEnum, Clone, Copy, Default)]
+enum DiffFormat {
+    /// The custom line-numbered, colorized inline rendering.
+    #[default]
+    Inline,
+    /// A standard unified diff understood by external tools and `patch(1)`.
+    Unified,
 }
 
 struct Line(Option<usize>);
@@ -97,8 +491,107 @@ impl std::fmt::Display for Line {
     }
 }
 
+/// Render a grouped, line-numbered, colorized inline diff between `old` and `new`.
+fn render_inline_diff(old: &str, new: &str) -> String {
+    use std::fmt::Write;
+    let mut out = String::new();
+    let diff = similar::TextDiff::from_lines(old, new);
+    for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
+        if idx > 0 {
+            let _ = writeln!(out, "{:-^1$}", "-", 80);
+        }
+        for op in group {
+            for change in diff.iter_inline_changes(op) {
+                let (sign, s) = match change.tag() {
+                    similar::ChangeTag::Delete => ("-", Style::new().red()),
+                    similar::ChangeTag::Insert => ("+", Style::new().green()),
+                    similar::ChangeTag::Equal => (" ", Style::new().dim()),
+                };
+                let _ = write!(
+                    out,
+                    "{}{} |{}",
+                    style(Line(change.old_index())).dim(),
+                    style(Line(change.new_index())).dim(),
+                    s.apply_to(sign).bold(),
+                );
+                for (emphasized, value) in change.iter_strings_lossy() {
+                    if emphasized {
+                        let _ = write!(out, "{}", s.apply_to(value).underlined().on_black());
+                    } else {
+                        let _ = write!(out, "{}", s.apply_to(value));
+                    }
+                }
+                if change.missing_newline() {
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Render a standard unified diff between `old` and `new`, with `@@` hunk
+/// headers and `---`/`+++` file lines that `patch(1)` and diff viewers understand.
+fn render_unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(old_name, new_name)
+        .to_string()
+}
+
+/// Write `content` to the user's pager when stdout is a TTY and a pager is
+/// configured (`pager` override, else `$PAGER`), falling back to printing directly.
+fn write_paged(content: &str, pager: Option<&str>) -> anyhow::Result<()> {
+    use std::io::Write;
+    if console::user_attended() {
+        let configured = pager
+            .map(ToString::to_string)
+            .or_else(|| std::env::var("PAGER").ok());
+        if let Some(pager) = configured.filter(|p| !p.trim().is_empty()) {
+            let mut parts = pager.split_whitespace();
+            if let Some(program) = parts.next() {
+                match std::process::Command::new(program)
+                    .args(parts)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                {
+                    Ok(mut child) => {
+                        // A pager is display-only: quitting it early closes the
+                        // pipe, so ignore write/wait failures rather than letting
+                        // them abort the command (and discard an unsaved patch).
+                        if let Some(stdin) = child.stdin.as_mut() {
+                            if let Err(e) = stdin.write_all(content.as_bytes()) {
+                                log::debug!("Pager closed its input early: {e}");
+                            }
+                        }
+                        if let Err(e) = child.wait() {
+                            log::warn!("Error waiting for pager: {e}");
+                        }
+                        return Ok(());
+                    }
+                    Err(e) => log::warn!("Failed to spawn pager {program:?}: {e}"),
+                }
+            }
+        }
+    }
+    print!("{content}");
+    Ok(())
+}
+
 fn edit(command: EditCommand) -> anyhow::Result<()> {
     let command = &command;
+    // Resolve defaults: CLI flags override config-file values override built-ins.
+    let config = load_config(command.config.as_deref())?;
+    let editor = command
+        .editor
+        .clone()
+        .or(config.editor)
+        .unwrap_or_else(|| "vim".to_string());
+    let watch_interval = Duration::from_millis(config.watch_interval_ms.unwrap_or(1000));
+    let indent = config.indent;
+    let pager = config.pager;
+
     // Create a temporary file
     let dir = tempfile::tempdir()?;
     let path = dir.path().join("patched.json");
@@ -106,10 +599,8 @@ fn edit(command: EditCommand) -> anyhow::Result<()> {
 
     let (patched, old_patch) =
         if std::fs::exists(&command.patch).context("Error checking whether patch file exists")? {
-            let patched = apply_impl(ApplyCommand {
-                input: command.input.clone(),
-                patch: command.patch.clone(),
-            })?;
+            let patched =
+                apply_impl(&command.input, &command.patch, command.merge, indent.as_deref(), None)?;
             let old_patch =
                 std::fs::read_to_string(&command.patch).context("Error reading patch file")?;
             (patched, old_patch)
@@ -124,10 +615,11 @@ fn edit(command: EditCommand) -> anyhow::Result<()> {
 
     std::thread::scope(|s| {
         if command.watch {
+            let watch_indent = indent.clone();
             s.spawn(move || {
                 let mut previous_final = None;
                 loop {
-                    std::thread::sleep(Duration::from_secs(1));
+                    std::thread::sleep(watch_interval);
 
                     if let Err(e) = (|| {
                         let current_final = std::fs::read_to_string(path)?;
@@ -141,6 +633,9 @@ fn edit(command: EditCommand) -> anyhow::Result<()> {
                         let new_patch = diff_impl(DiffCommand {
                             from: command.input.clone(),
                             to: path.clone(),
+                            merge: command.merge,
+                            indent: watch_indent.clone(),
+                            print_sha256: false,
                         })
                         .context("Error executing diff")?;
                         std::fs::write(command.patch.clone(), new_patch)?;
@@ -154,10 +649,10 @@ fn edit(command: EditCommand) -> anyhow::Result<()> {
             });
         }
 
-        log::debug!("Editing {path:?} with {}", &command.editor);
+        log::debug!("Editing {path:?} with {editor}");
 
         // Spawn Vim as a child process
-        std::process::Command::new(&command.editor)
+        std::process::Command::new(&editor)
             .arg(path)
             .stdin(Stdio::inherit())
             .stdout(Stdio::inherit())
@@ -168,40 +663,20 @@ fn edit(command: EditCommand) -> anyhow::Result<()> {
         let new_patch = diff_impl(DiffCommand {
             from: command.input.clone(),
             to: path.clone(),
+            merge: command.merge,
+            indent: indent.clone(),
+            print_sha256: false,
         })
         .context("Error executing diff")?;
 
-        let diff = similar::TextDiff::from_lines(&old_patch, &new_patch);
-        for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
-            if idx > 0 {
-                println!("{:-^1$}", "-", 80);
-            }
-            for op in group {
-                for change in diff.iter_inline_changes(op) {
-                    let (sign, s) = match change.tag() {
-                        similar::ChangeTag::Delete => ("-", Style::new().red()),
-                        similar::ChangeTag::Insert => ("+", Style::new().green()),
-                        similar::ChangeTag::Equal => (" ", Style::new().dim()),
-                    };
-                    print!(
-                        "{}{} |{}",
-                        style(Line(change.old_index())).dim(),
-                        style(Line(change.new_index())).dim(),
-                        s.apply_to(sign).bold(),
-                    );
-                    for (emphasized, value) in change.iter_strings_lossy() {
-                        if emphasized {
-                            print!("{}", s.apply_to(value).underlined().on_black());
-                        } else {
-                            print!("{}", s.apply_to(value));
-                        }
-                    }
-                    if change.missing_newline() {
-                        println!();
-                    }
-                }
+        let rendered = match command.format {
+            DiffFormat::Inline => render_inline_diff(&old_patch, &new_patch),
+            DiffFormat::Unified => {
+                let patch_name = command.patch.display().to_string();
+                render_unified_diff(&old_patch, &new_patch, &patch_name, &patch_name)
             }
-        }
+        };
+        write_paged(&rendered, pager.as_deref())?;
 
         std::fs::write(command.patch.clone(), new_patch)?;
 
@@ -231,3 +706,54 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_apply_recurses_and_deletes_nulls() {
+        let mut target = json!({"a": 1, "b": {"c": 2, "keep": true}});
+        merge_apply(&mut target, &json!({"b": {"c": null, "d": 3}, "e": 4}));
+        assert_eq!(target, json!({"a": 1, "b": {"keep": true, "d": 3}, "e": 4}));
+    }
+
+    #[test]
+    fn merge_apply_replaces_when_patch_is_not_object() {
+        let mut target = json!({"a": 1});
+        merge_apply(&mut target, &json!(42));
+        assert_eq!(target, json!(42));
+    }
+
+    #[test]
+    fn merge_diff_then_apply_round_trips() {
+        let from = json!({"keep": 1, "change": {"x": 1}, "drop": "bye"});
+        let to = json!({"keep": 1, "change": {"x": 2, "y": 3}, "add": true});
+        let patch = merge_diff(&from, &to);
+        let mut patched = from.clone();
+        merge_apply(&mut patched, &patch);
+        assert_eq!(patched, to);
+    }
+
+    #[test]
+    fn merge_diff_emits_null_for_removed_keys() {
+        let from = json!({"a": 1, "b": 2});
+        let to = json!({"a": 1});
+        assert_eq!(merge_diff(&from, &to), json!({"b": null}));
+    }
+
+    #[test]
+    fn sha256_hex_matches_known_vectors() {
+        assert_eq!(
+            sha256_hex(&json!({})),
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        // Keys are canonicalized (sorted) before hashing, so insertion order
+        // does not change the digest.
+        assert_eq!(
+            sha256_hex(&json!({"b": 1, "a": 2})),
+            "d3626ac30a87e6f7a6428233b3c68299976865fa5508e4267c5415c76af7a772"
+        );
+    }
+}